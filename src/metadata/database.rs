@@ -29,17 +29,195 @@ use metadata::loader;
 /// The Google provided metadata database, used as default.
 const DATABASE: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/database.bin"));
 
+/// Carrier name lookup table, keyed by locale and numeric prefix.
+const CARRIER: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/carrier.bin"));
+
+/// Geographical description lookup table, keyed by locale and numeric prefix.
+const GEOCODING: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/geocoding.bin"));
+
+/// Region id libphonenumber uses in place of a real region for calling
+/// codes that are non-geographic numbering plans (e.g. `800` international
+/// freephone, `808`, `870`, `878`, `881`) rather than a list of countries.
+const NON_GEOGRAPHIC_REGION: &str = "001";
+
 lazy_static! {
-	pub static ref DEFAULT: Database =
-		Database::from(bincode::deserialize(DATABASE).unwrap()).unwrap();
+	pub static ref DEFAULT: Database = {
+		let mut database = Database::from(bincode::deserialize(DATABASE).unwrap()).unwrap();
+
+		database.carrier   = load_prefixes(CARRIER).unwrap();
+		database.geocoding = load_prefixes(GEOCODING).unwrap();
+
+		database
+	};
+}
+
+/// A single entry in a prefix-keyed auxiliary table (carrier name or
+/// geographical description).
+#[derive(Debug, Deserialize)]
+struct PrefixEntry {
+	/// Locale the text applies to (e.g. `en`, `en-US`).
+	locale: String,
+
+	/// Country code followed by leading national significant digits.
+	prefix: u64,
+
+	/// Display text associated with the prefix for the given locale.
+	text: String,
+}
+
+/// Load a prefix-keyed auxiliary table, grouping entries by locale.
+fn load_prefixes(bytes: &[u8]) -> Result<FnvHashMap<String, FnvHashMap<u64, String>>> {
+	let entries: Vec<PrefixEntry> = bincode::deserialize(bytes)?;
+	let mut table = FnvHashMap::default();
+
+	for entry in entries {
+		table.entry(entry.locale).or_insert_with(FnvHashMap::default)
+			.insert(entry.prefix, entry.text);
+	}
+
+	Ok(table)
+}
+
+fn switch<T>(value: Option<Result<T>>) -> Result<Option<T>> {
+	match value {
+		None =>
+			Ok(None),
+
+		Some(Ok(value)) =>
+			Ok(Some(value)),
+
+		Some(Err(err)) =>
+			Err(err),
+	}
+}
+
+fn regex(value: String) -> Result<LazyRegex> {
+	Ok(LazyRegexBuilder::new(&value).ignore_whitespace(true).build()?)
+}
+
+fn metadata(meta: loader::Metadata) -> Result<super::Metadata> {
+	Ok(super::Metadata {
+		descriptors: super::Descriptors {
+			general: descriptor(meta.general.ok_or_else(||
+				Error::from(error::Metadata::MissingValue {
+					phase: "metadata".into(),
+					name:  "generalDesc".into(),
+				}))?)?,
+
+			fixed_line:       switch(meta.fixed_line.map(descriptor))?,
+			mobile:           switch(meta.mobile.map(descriptor))?,
+			toll_free:        switch(meta.toll_free.map(descriptor))?,
+			premium_rate:     switch(meta.premium_rate.map(descriptor))?,
+			shared_cost:      switch(meta.shared_cost.map(descriptor))?,
+			personal_number:  switch(meta.personal_number.map(descriptor))?,
+			voip:             switch(meta.voip.map(descriptor))?,
+			pager:            switch(meta.pager.map(descriptor))?,
+			uan:              switch(meta.uan.map(descriptor))?,
+			emergency:        switch(meta.emergency.map(descriptor))?,
+			voicemail:        switch(meta.voicemail.map(descriptor))?,
+			short_code:       switch(meta.short_code.map(descriptor))?,
+			standard_rate:    switch(meta.standard_rate.map(descriptor))?,
+			carrier:          switch(meta.carrier.map(descriptor))?,
+			no_international: switch(meta.no_international.map(descriptor))?,
+		},
+
+		id: meta.id.ok_or_else(||
+			Error::from(error::Metadata::MissingValue {
+				phase: "metadata".into(),
+				name:  "id".into()
+			}))?,
+
+		country_code: meta.country_code.ok_or_else(||
+			Error::from(error::Metadata::MissingValue {
+				phase: "metadata".into(),
+				name: "countryCode".into(),
+			}))?,
+
+		international_prefix: switch(meta.international_prefix.map(regex))?,
+		preferred_international_prefix: meta.preferred_international_prefix,
+		national_prefix: meta.national_prefix,
+		preferred_extension_prefix: meta.preferred_extension_prefix,
+		national_prefix_for_parsing: switch(meta.national_prefix_for_parsing.map(regex))?,
+		national_prefix_transform_rule: meta.national_prefix_transform_rule,
+
+		format: meta.format.into_iter().map(format).collect::<Result<_>>()?,
+		international_format: meta.international_format.into_iter().map(format).collect::<Result<_>>()?,
+
+		main_country_for_code: meta.main_country_for_code,
+		leading_digits: switch(meta.leading_digits.map(regex))?,
+		mobile_number_portable: meta.mobile_number_portable,
+	})
+}
+
+fn descriptor(desc: loader::Descriptor) -> Result<super::Descriptor> {
+	Ok(super::Descriptor {
+		national_number: desc.national_number.ok_or_else(||
+			Error::from(error::Metadata::MissingValue {
+				phase: "descriptor".into(),
+				name:  "national_number".into(),
+			})).and_then(regex)?,
+
+		possible_number: switch(desc.possible_number.map(regex))?,
+		possible_length: desc.possible_length,
+		possible_local_length: desc.possible_local_length,
+		example: desc.example,
+	})
+}
+
+fn format(format: loader::Format) -> Result<super::Format> {
+	Ok(super::Format {
+		pattern: format.pattern.ok_or_else(||
+			Error::from(error::Metadata::MissingValue {
+				phase: "format".into(),
+				name:  "pattern".into(),
+			})).and_then(regex)?,
+
+		format: format.format.ok_or_else(||
+			Error::from(error::Metadata::MissingValue {
+				phase: "format".into(),
+				name:  "format".into()
+			}))?,
+
+		leading_digits: format.leading_digits.into_iter()
+			.map(regex).collect::<Result<_>>()?,
+
+		national_prefix: format.national_prefix,
+		domestic_carrier: format.domestic_carrier,
+	})
 }
 
 /// Representation of a database of metadata for phone number.
+///
+/// Metadata is compiled -- building each descriptor's `LazyRegex`es -- once,
+/// at construction, and kept behind cheaply-cloneable `Arc`s, so `by_id`,
+/// `by_code` and `non_geographic` can hand out plain borrows.
 #[derive(Clone, Debug)]
 pub struct Database {
 	by_id:   FnvHashMap<String, Arc<super::Metadata>>,
 	by_code: FnvHashMap<u16, Vec<Arc<super::Metadata>>>,
+
+	/// Country code -> region ids (excludes non-geographic entries).
 	regions: FnvHashMap<u16, Vec<String>>,
+
+	/// Country code -> the non-geographic numbering plan entity for that
+	/// code (e.g. `800` international freephone), for calling codes that
+	/// aren't a list of countries. Kept out of `by_id`/`by_code`/`regions`,
+	/// since their id is the shared `NON_GEOGRAPHIC_REGION` sentinel, not a
+	/// meaningful per-region identifier.
+	non_geographic: FnvHashMap<u16, Arc<super::Metadata>>,
+
+	/// Region id -> calling code, for the region ids held in `by_id`; read by
+	/// `calling_code_for`. It has no entry for a region id that is not
+	/// itself in `by_id`.
+	codes: FnvHashMap<String, u16>,
+
+	carrier:   FnvHashMap<String, FnvHashMap<u64, String>>,
+	geocoding: FnvHashMap<String, FnvHashMap<u64, String>>,
+
+	/// Alternate groupings for numbers, used only when scanning free text:
+	/// many numbers are written with spacing that the regular `format`/
+	/// `international_format` patterns reject outright.
+	alternate_formats: FnvHashMap<u16, Vec<super::Format>>,
 }
 
 impl Database {
@@ -55,133 +233,61 @@ impl Database {
 
 	/// Create a database from a loaded database.
 	pub fn from(meta: Vec<loader::Metadata>) -> Result<Self> {
-		fn switch<T>(value: Option<Result<T>>) -> Result<Option<T>> {
-			match value {
-				None =>
-					Ok(None),
-
-				Some(Ok(value)) =>
-					Ok(Some(value)),
-
-				Some(Err(err)) =>
-					Err(err),
-			}
-		}
+		Database::from_with_alternates(meta, None)
+	}
 
-		fn regex(value: String) -> Result<LazyRegex> {
-			Ok(LazyRegexBuilder::new(&value).ignore_whitespace(true).build()?)
-		}
+	/// Create a database from a loaded database, optionally also loading a
+	/// second metadata source holding the alternate-format groupings used
+	/// when matching numbers in free text.
+	pub fn from_with_alternates(meta: Vec<loader::Metadata>,
+		alternate: Option<Vec<loader::Metadata>>) -> Result<Self>
+	{
+		let mut by_id          = FnvHashMap::default();
+		let mut by_code        = FnvHashMap::default();
+		let mut regions        = FnvHashMap::default();
+		let mut non_geographic = FnvHashMap::default();
+		let mut codes          = FnvHashMap::default();
 
-		fn metadata(meta: loader::Metadata) -> Result<super::Metadata> {
-			Ok(super::Metadata {
-				descriptors: super::Descriptors {
-					general: descriptor(meta.general.ok_or_else(||
-						Error::from(error::Metadata::MissingValue {
-							phase: "metadata".into(),
-							name:  "generalDesc".into(),
-						}))?)?,
-
-					fixed_line:       switch(meta.fixed_line.map(descriptor))?,
-					mobile:           switch(meta.mobile.map(descriptor))?,
-					toll_free:        switch(meta.toll_free.map(descriptor))?,
-					premium_rate:     switch(meta.premium_rate.map(descriptor))?,
-					shared_cost:      switch(meta.shared_cost.map(descriptor))?,
-					personal_number:  switch(meta.personal_number.map(descriptor))?,
-					voip:             switch(meta.voip.map(descriptor))?,
-					pager:            switch(meta.pager.map(descriptor))?,
-					uan:              switch(meta.uan.map(descriptor))?,
-					emergency:        switch(meta.emergency.map(descriptor))?,
-					voicemail:        switch(meta.voicemail.map(descriptor))?,
-					short_code:       switch(meta.short_code.map(descriptor))?,
-					standard_rate:    switch(meta.standard_rate.map(descriptor))?,
-					carrier:          switch(meta.carrier.map(descriptor))?,
-					no_international: switch(meta.no_international.map(descriptor))?,
-				},
-
-				id: meta.id.ok_or_else(||
-					Error::from(error::Metadata::MissingValue {
-						phase: "metadata".into(),
-						name:  "id".into()
-					}))?,
-
-				country_code: meta.country_code.ok_or_else(||
-					Error::from(error::Metadata::MissingValue {
-						phase: "metadata".into(),
-						name: "countryCode".into(),
-					}))?,
-
-				international_prefix: switch(meta.international_prefix.map(regex))?,
-				preferred_international_prefix: meta.preferred_international_prefix,
-				national_prefix: meta.national_prefix,
-				preferred_extension_prefix: meta.preferred_extension_prefix,
-				national_prefix_for_parsing: switch(meta.national_prefix_for_parsing.map(regex))?,
-				national_prefix_transform_rule: meta.national_prefix_transform_rule,
-
-				format: meta.format.into_iter().map(format).collect::<Result<_>>()?,
-				international_format: meta.international_format.into_iter().map(format).collect::<Result<_>>()?,
-
-				main_country_for_code: meta.main_country_for_code,
-				leading_digits: switch(meta.leading_digits.map(regex))?,
-				mobile_number_portable: meta.mobile_number_portable,
-			})
-		}
+		for meta in meta {
+			let meta = Arc::new(metadata(meta)?);
 
-		fn descriptor(desc: loader::Descriptor) -> Result<super::Descriptor> {
-			desc.national_number.as_ref().unwrap();
-			desc.national_number.as_ref().unwrap();
-
-			Ok(super::Descriptor {
-				national_number: desc.national_number.ok_or_else(||
-					Error::from(error::Metadata::MissingValue {
-						phase: "descriptor".into(),
-						name:  "national_number".into(),
-					})).and_then(regex)?,
-
-				possible_number: switch(desc.possible_number.map(regex))?,
-				possible_length: desc.possible_length,
-				possible_local_length: desc.possible_local_length,
-				example: desc.example,
-			})
+			if meta.id == NON_GEOGRAPHIC_REGION {
+				non_geographic.insert(meta.country_code, meta);
+			}
+			else {
+				by_code.entry(meta.country_code).or_insert_with(Vec::new).push(meta.clone());
+				regions.entry(meta.country_code).or_insert_with(Vec::new).push(meta.id.clone());
+				codes.insert(meta.id.clone(), meta.country_code);
+				by_id.insert(meta.id.clone(), meta);
+			}
 		}
 
-		fn format(format: loader::Format) -> Result<super::Format> {
-			Ok(super::Format {
-				pattern: format.pattern.ok_or_else(||
-					Error::from(error::Metadata::MissingValue {
-						phase: "format".into(),
-						name:  "pattern".into(),
-					})).and_then(regex)?,
-
-				format: format.format.ok_or_else(||
-					Error::from(error::Metadata::MissingValue {
-						phase: "format".into(),
-						name:  "format".into()
-					}))?,
-
-				leading_digits: format.leading_digits.into_iter()
-					.map(regex).collect::<Result<_>>()?,
-
-				national_prefix: format.national_prefix,
-				domestic_carrier: format.domestic_carrier,
-			})
-		}
+		let mut alternate_formats = FnvHashMap::default();
 
-		let mut by_id   = FnvHashMap::default();
-		let mut by_code = FnvHashMap::default();
-		let mut regions = FnvHashMap::default();
+		for meta in alternate.into_iter().flatten() {
+			let code = meta.country_code.ok_or_else(||
+				Error::from(error::Metadata::MissingValue {
+					phase: "metadata".into(),
+					name: "countryCode".into(),
+				}))?;
 
-		for meta in meta {
-			let meta = Arc::new(metadata(meta)?);
+			let formats = meta.format.into_iter().map(format).collect::<Result<Vec<_>>>()?;
 
-			by_id.insert(meta.id.clone(), meta.clone());
-			by_code.entry(meta.country_code).or_insert_with(Vec::new).push(meta.clone());
-			regions.entry(meta.country_code).or_insert_with(Vec::new).push(meta.id.clone());
+			alternate_formats.entry(code).or_insert_with(Vec::new).extend(formats);
 		}
 
 		Ok(Database {
 			by_id:   by_id,
 			by_code: by_code,
 			regions: regions,
+
+			non_geographic: non_geographic,
+			codes:          codes,
+
+			carrier:   FnvHashMap::default(),
+			geocoding: FnvHashMap::default(),
+
+			alternate_formats: alternate_formats,
 		})
 	}
 
@@ -194,6 +300,10 @@ impl Database {
 	}
 
 	/// Get metadata entries by country code.
+	///
+	/// Only returns geographic regions; a purely non-geographic numbering
+	/// plan (e.g. `800`) isn't in here even though it has the same kind of
+	/// calling code -- use `non_geographic`/`is_non_geographic` for those.
 	pub fn by_code<Q>(&self, key: &Q) -> Option<Vec<&super::Metadata>>
 		where Q:   ?Sized + Hash + Eq,
 		      u16: Borrow<Q>,
@@ -202,10 +312,420 @@ impl Database {
 	}
 
 	/// Get all country IDs corresponding to the given country code.
+	///
+	/// Returns `None` both when the code is unknown and when it is a
+	/// non-geographic numbering plan (see `is_non_geographic`); a genuine
+	/// geographic region list is never empty.
 	pub fn region<Q>(&self, code: &Q) -> Option<Vec<&str>>
 		where Q:   ?Sized + Hash + Eq,
 		      u16: Borrow<Q>
 	{
 		self.regions.get(code).map(|m| m.iter().map(AsRef::as_ref).collect())
 	}
+
+	/// Get the non-geographic numbering plan entity for the given calling
+	/// code, if that code is a non-geographic one (e.g. `800`
+	/// international freephone) rather than a list of countries.
+	pub fn non_geographic<Q>(&self, code: &Q) -> Option<&super::Metadata>
+		where Q:   ?Sized + Hash + Eq,
+		      u16: Borrow<Q>,
+	{
+		self.non_geographic.get(code).map(AsRef::as_ref)
+	}
+
+	/// Check whether the given calling code is a non-geographic numbering
+	/// plan rather than a list of countries.
+	pub fn is_non_geographic<Q>(&self, code: &Q) -> bool
+		where Q:   ?Sized + Hash + Eq,
+		      u16: Borrow<Q>,
+	{
+		self.non_geographic.contains_key(code)
+	}
+
+	/// Get the alternate format groupings for the given country code, if
+	/// any were loaded via `from_with_alternates`.
+	///
+	/// These are only meant to be tried, in addition to a region's regular
+	/// `format`/`international_format` patterns, when matching numbers
+	/// found in free text.
+	pub fn alternate_formats<Q>(&self, code: &Q) -> Option<&[super::Format]>
+		where Q:   ?Sized + Hash + Eq,
+		      u16: Borrow<Q>,
+	{
+		self.alternate_formats.get(code).map(Vec::as_slice)
+	}
+
+	/// Get the carrier name for the given number, in the given locale.
+	///
+	/// Looks up progressively shorter prefixes of the number's country code
+	/// followed by its national significant number, trying `locale`, then
+	/// its base language (e.g. `en-US` -> `en`), then the empty locale.
+	pub fn carrier_for(&self, number: &super::super::PhoneNumber, locale: &str) -> Option<&str> {
+		self.lookup_prefix(&self.carrier, number, locale)
+	}
+
+	/// Get a geographical description of where the given number is
+	/// registered, in the given locale.
+	///
+	/// Uses the same prefix and locale fallback rules as `carrier_for`.
+	pub fn description_for(&self, number: &super::super::PhoneNumber, locale: &str) -> Option<&str> {
+		self.lookup_prefix(&self.geocoding, number, locale)
+	}
+
+	fn lookup_prefix<'a>(&'a self, table: &'a FnvHashMap<String, FnvHashMap<u64, String>>,
+		number: &super::super::PhoneNumber, locale: &str) -> Option<&'a str>
+	{
+		let prefix = prefix_digits(number.country_code().value(),
+			number.national_number().value(), number.national_number().zeros())?;
+
+		for locale in locale_chain(locale) {
+			if let Some(by_prefix) = table.get(&locale) {
+				let mut digits = prefix;
+
+				while digits > 0 {
+					if let Some(text) = by_prefix.get(&digits) {
+						return Some(text.as_str());
+					}
+
+					digits /= 10;
+				}
+			}
+		}
+
+		None
+	}
+}
+
+/// Build the `carrier`/`geocoding` table key for a number: its calling code
+/// followed by its national significant number, written out as digits rather
+/// than as the bare `u64` value so that a leading-zero national number (e.g.
+/// Italian-style `0`-prefixed numbers) keeps its zeros instead of silently
+/// losing them.
+fn prefix_digits(country_code: u16, national_number: u64, leading_zeros: u8) -> Option<u64> {
+	format!("{}{}{}", country_code, "0".repeat(leading_zeros as usize), national_number)
+		.parse().ok()
+}
+
+/// Build the locale fallback chain used by `carrier_for`/`description_for`,
+/// e.g. `en-US` -> `en` -> "".
+fn locale_chain(locale: &str) -> Vec<String> {
+	let mut chain = vec![locale.to_string()];
+
+	if let Some(index) = locale.find('-') {
+		chain.push(locale[..index].to_string());
+	}
+
+	chain.push(String::new());
+	chain
+}
+
+/// A source of phone number metadata.
+///
+/// Implemented by `Database` itself, and by the `ForkProvider`/
+/// `FallbackProvider` adapters below, so a custom or hot-swappable metadata
+/// source can be used instead of always going through the global `DEFAULT`.
+pub trait MetadataProvider {
+	/// Get a metadata entry by country ID.
+	fn by_id(&self, id: &str) -> Option<&super::Metadata>;
+
+	/// Get metadata entries by country code.
+	fn by_code(&self, code: u16) -> Option<Vec<&super::Metadata>>;
+
+	/// Get all country IDs corresponding to the given country code.
+	fn region(&self, code: u16) -> Option<Vec<&str>>;
+
+	/// Get the calling code a country ID belongs to, if known.
+	///
+	/// Used by `FallbackProvider` to resolve a missing region ID back to the
+	/// calling code whose main-country entry it should fall back to.
+	fn calling_code_for(&self, id: &str) -> Option<u16>;
+}
+
+impl MetadataProvider for Database {
+	fn by_id(&self, id: &str) -> Option<&super::Metadata> {
+		Database::by_id(self, id)
+	}
+
+	fn by_code(&self, code: u16) -> Option<Vec<&super::Metadata>> {
+		Database::by_code(self, &code)
+	}
+
+	fn region(&self, code: u16) -> Option<Vec<&str>> {
+		Database::region(self, &code)
+	}
+
+	fn calling_code_for(&self, id: &str) -> Option<u16> {
+		self.codes.get(id).cloned()
+	}
+}
+
+/// A provider that queries an ordered list of inner providers and returns
+/// the first one that yields a hit.
+///
+/// This lets an application overlay a small, patched database on top of the
+/// baked-in one without having to fork or recompile it.
+pub struct ForkProvider<P> {
+	providers: Vec<P>,
+}
+
+impl<P: MetadataProvider> ForkProvider<P> {
+	/// Create a provider that tries each of `providers` in order.
+	pub fn new(providers: Vec<P>) -> Self {
+		ForkProvider {
+			providers: providers,
+		}
+	}
+}
+
+impl<P: MetadataProvider> MetadataProvider for ForkProvider<P> {
+	fn by_id(&self, id: &str) -> Option<&super::Metadata> {
+		self.providers.iter().filter_map(|provider| provider.by_id(id)).next()
+	}
+
+	fn by_code(&self, code: u16) -> Option<Vec<&super::Metadata>> {
+		self.providers.iter().filter_map(|provider| provider.by_code(code)).next()
+	}
+
+	fn region(&self, code: u16) -> Option<Vec<&str>> {
+		self.providers.iter().filter_map(|provider| provider.region(code)).next()
+	}
+
+	fn calling_code_for(&self, id: &str) -> Option<u16> {
+		self.providers.iter().filter_map(|provider| provider.calling_code_for(id)).next()
+	}
+}
+
+/// A provider that falls back to the main country for a calling code when a
+/// specific region ID is not found.
+///
+/// For example, if the inner provider's `by_id` misses `US` but its
+/// `calling_code_for("US")` gives `1`, and `1` has an entry flagged with
+/// `main_country_for_code`, looking up `US` through this provider yields
+/// that entry. A `Database` only knows the calling codes of the region IDs
+/// it holds, so over a `Database` (or a `ForkProvider` of them) a missing
+/// region ID is not resolved and `by_id` returns `None`.
+pub struct FallbackProvider<P> {
+	inner: P,
+}
+
+impl<P: MetadataProvider> FallbackProvider<P> {
+	/// Wrap `inner`, adding main-country-for-code fallback to its `by_id`
+	/// lookups.
+	pub fn new(inner: P) -> Self {
+		FallbackProvider {
+			inner: inner,
+		}
+	}
+}
+
+impl<P: MetadataProvider> MetadataProvider for FallbackProvider<P> {
+	fn by_id(&self, id: &str) -> Option<&super::Metadata> {
+		self.inner.by_id(id).or_else(|| {
+			let code = self.inner.calling_code_for(id)?;
+
+			self.inner.by_code(code)?.into_iter().find(|meta| meta.main_country_for_code)
+		})
+	}
+
+	fn by_code(&self, code: u16) -> Option<Vec<&super::Metadata>> {
+		self.inner.by_code(code)
+	}
+
+	fn region(&self, code: u16) -> Option<Vec<&str>> {
+		self.inner.region(code)
+	}
+
+	fn calling_code_for(&self, id: &str) -> Option<u16> {
+		self.inner.calling_code_for(id)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::cell::Cell;
+
+	use super::*;
+
+	fn empty_database() -> Database {
+		Database {
+			by_id:   FnvHashMap::default(),
+			by_code: FnvHashMap::default(),
+			regions: FnvHashMap::default(),
+
+			non_geographic: FnvHashMap::default(),
+			codes:          FnvHashMap::default(),
+
+			carrier:   FnvHashMap::default(),
+			geocoding: FnvHashMap::default(),
+
+			alternate_formats: FnvHashMap::default(),
+		}
+	}
+
+	#[test]
+	fn locale_chain_falls_back_through_base_language_to_empty() {
+		assert_eq!(locale_chain("en-US"), vec!["en-US", "en", ""]);
+		assert_eq!(locale_chain("en"), vec!["en", ""]);
+	}
+
+	#[test]
+	fn prefix_digits_keeps_leading_zeros_of_the_national_number() {
+		// A national significant number of `79` with one leading zero under
+		// calling code `380` must look up `380079`, not `38079` -- the zero
+		// isn't just cosmetic padding.
+		assert_eq!(prefix_digits(380, 79, 1), Some(380079));
+		assert_eq!(prefix_digits(256, 727, 0), Some(256727));
+	}
+
+	#[test]
+	fn by_id_and_by_code_miss_return_none() {
+		let database = empty_database();
+
+		assert!(database.by_id("US").is_none());
+		assert!(database.by_code(&1u16).is_none());
+	}
+
+	#[test]
+	fn calling_code_for_reads_the_id_to_code_map() {
+		let mut database = empty_database();
+		database.codes.insert("US".into(), 1);
+
+		assert_eq!(MetadataProvider::calling_code_for(&database, "US"), Some(1));
+		assert_eq!(MetadataProvider::calling_code_for(&database, "ZZ"), None);
+	}
+
+	/// A minimal but otherwise-valid `loader::Metadata` for the given region
+	/// id and calling code, enough to pass `metadata()`'s validation.
+	fn fixture(id: &str, country_code: u16) -> loader::Metadata {
+		loader::Metadata {
+			general: Some(loader::Descriptor {
+				national_number:       Some(String::new()),
+				possible_number:       None,
+				possible_length:       Vec::new(),
+				possible_local_length: Vec::new(),
+				example:               None,
+			}),
+
+			fixed_line:       None,
+			mobile:           None,
+			toll_free:        None,
+			premium_rate:     None,
+			shared_cost:      None,
+			personal_number:  None,
+			voip:             None,
+			pager:            None,
+			uan:              None,
+			emergency:        None,
+			voicemail:        None,
+			short_code:       None,
+			standard_rate:    None,
+			carrier:          None,
+			no_international: None,
+
+			id:           Some(id.into()),
+			country_code: Some(country_code),
+
+			international_prefix:           None,
+			preferred_international_prefix: None,
+			national_prefix:                None,
+			preferred_extension_prefix:     None,
+			national_prefix_for_parsing:    None,
+			national_prefix_transform_rule: None,
+
+			format:               Vec::new(),
+			international_format: Vec::new(),
+
+			main_country_for_code:  true,
+			leading_digits:         None,
+			mobile_number_portable: false,
+		}
+	}
+
+	#[test]
+	fn by_code_excludes_non_geographic_entries() {
+		let database = Database::from(vec![fixture(NON_GEOGRAPHIC_REGION, 800)]).unwrap();
+
+		assert!(database.is_non_geographic(&800u16));
+		assert!(database.by_code(&800u16).is_none());
+		assert!(database.by_id(NON_GEOGRAPHIC_REGION).is_none());
+		assert!(database.non_geographic(&800u16).is_some());
+	}
+
+	/// A provider whose `by_id` always misses, so lookups fall all the way
+	/// through to `calling_code_for`/`by_code`; records which calling code,
+	/// if any, `by_code` was asked for.
+	struct MockProvider {
+		id:             &'static str,
+		code:           Option<u16>,
+		by_code_called: Cell<Option<u16>>,
+	}
+
+	impl MetadataProvider for MockProvider {
+		fn by_id(&self, _id: &str) -> Option<&super::super::Metadata> {
+			None
+		}
+
+		fn by_code(&self, code: u16) -> Option<Vec<&super::super::Metadata>> {
+			self.by_code_called.set(Some(code));
+			Some(Vec::new())
+		}
+
+		fn region(&self, _code: u16) -> Option<Vec<&str>> {
+			None
+		}
+
+		fn calling_code_for(&self, id: &str) -> Option<u16> {
+			if id == self.id { self.code } else { None }
+		}
+	}
+
+	#[test]
+	fn fallback_provider_resolves_alpha_id_via_calling_code_map() {
+		let inner    = MockProvider { id: "US", code: Some(1), by_code_called: Cell::new(None) };
+		let provider = FallbackProvider::new(inner);
+
+		// Regression test for the bug where `id.parse::<u16>()` silently
+		// failed for every real (alpha) region id, so `by_code` was never
+		// even consulted for the fallback.
+		provider.by_id("US");
+
+		assert_eq!(provider.inner.by_code_called.get(), Some(1));
+	}
+
+	#[test]
+	fn fallback_provider_gives_up_when_id_has_no_calling_code() {
+		let inner    = MockProvider { id: "US", code: None, by_code_called: Cell::new(None) };
+		let provider = FallbackProvider::new(inner);
+
+		assert!(provider.by_id("ZZ").is_none());
+		assert_eq!(provider.inner.by_code_called.get(), None);
+	}
+
+	#[test]
+	fn fork_provider_returns_the_first_hit() {
+		struct Fixed(Option<u16>);
+
+		impl MetadataProvider for Fixed {
+			fn by_id(&self, _id: &str) -> Option<&super::super::Metadata> {
+				None
+			}
+
+			fn by_code(&self, _code: u16) -> Option<Vec<&super::super::Metadata>> {
+				self.0.map(|_| Vec::new())
+			}
+
+			fn region(&self, _code: u16) -> Option<Vec<&str>> {
+				None
+			}
+
+			fn calling_code_for(&self, _id: &str) -> Option<u16> {
+				self.0
+			}
+		}
+
+		let provider = ForkProvider::new(vec![Fixed(None), Fixed(Some(1)), Fixed(Some(2))]);
+
+		assert!(provider.by_code(0).is_some());
+		assert_eq!(provider.calling_code_for("US"), Some(1));
+	}
 }